@@ -1,7 +1,8 @@
 use clap::{arg, command};
+use std::collections::VecDeque;
 use std::error::Error;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader, Read};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
 
 #[derive(Debug)]
 enum HeaderChoice {
@@ -15,10 +16,12 @@ pub struct Config {
     files: Vec<String>,
     lines: i128,
     bytes: Option<i128>,
-    print_header: HeaderChoice
+    print_header: HeaderChoice,
+    zero_terminated: bool
 }
 
 type RunResult<T> = Result<T, Box<dyn Error>>;
+type OpenResult = (Box<dyn BufRead>, Option<usize>, Option<usize>);
 
 pub fn get_args() -> RunResult<Config> {
     let matches = command!()
@@ -34,7 +37,8 @@ pub fn get_args() -> RunResult<Config> {
                 .alias("silent")
                 .conflicts_with("verbose"),
             arg!(verbose: -v --verbose "always print headers giving file names")
-                .conflicts_with("quiet")
+                .conflicts_with("quiet"),
+            arg!(zero_terminated: -z --"zero-terminated" "line delimiter is NUL, not newline")
         ])
         .get_matches();
     
@@ -70,14 +74,17 @@ pub fn get_args() -> RunResult<Config> {
             HeaderChoice::Always
         } else {
             HeaderChoice::Multiple
-        }
+        },
+        zero_terminated: matches.get_flag("zero_terminated")
     })
 }
 
-pub fn run(config: Config) -> RunResult<()> {
+pub fn run(config: Config, writer: &mut impl Write) -> RunResult<()> {
     let num_files = config.files.len();
+    let need_line_count = config.bytes.is_none() && config.lines < 0;
+    let delim = if config.zero_terminated { b'\0' } else { b'\n' };
     for (file_num, filename) in config.files.iter().enumerate() {
-        let (mut file, size, line_count) = match open(&filename) {
+        let (mut file, size, line_count) = match open(filename, need_line_count, delim) {
             Ok((file, size, line_count)) => (file, size, line_count),
             Err(err) => {
                 eprintln!("{filename}: {err}");
@@ -92,54 +99,130 @@ pub fn run(config: Config) -> RunResult<()> {
         };
 
         if print_header {
-            println!(
+            writeln!(
+                writer,
                 "{}==> {} <==",
                 if file_num > 0 { "\n" } else { "" },
                 &filename
-            );
+            )?;
         }
 
         if let Some(num_bytes) = config.bytes {
-            let bytes: Result<Vec<u8>, _> =
-                file.bytes().take(
-                    if num_bytes < 0 {
-                        size as i128 + num_bytes
-                    } else { num_bytes } as usize
-                ).collect();
-            print!("{}", String::from_utf8_lossy(&bytes?));
+            if num_bytes < 0 {
+                match size {
+                    Some(size) => {
+                        let take = (size as i128 + num_bytes).max(0) as usize;
+                        let bytes: Result<Vec<u8>, _> =
+                            file.bytes().take(take).collect();
+                        writer.write_all(&bytes?)?;
+                    }
+                    None => print_all_but_last_n_bytes(&mut file, writer, (-num_bytes) as usize)?
+                }
+            } else {
+                let bytes: Result<Vec<u8>, _> =
+                    file.bytes().take(num_bytes as usize).collect();
+                writer.write_all(&bytes?)?;
+            }
+        } else if config.lines < 0 {
+            match line_count {
+                Some(line_count) => {
+                    let num_lines = line_count as i128 + config.lines;
+                    let mut buf: Vec<u8> = Vec::new();
+                    for _ in 0..num_lines {
+                        let bytes = file.read_until(delim, &mut buf)?;
+                        if bytes == 0 {
+                            break;
+                        }
+                        writer.write_all(&buf)?;
+                        buf.clear();
+                    }
+                }
+                None => print_all_but_last_n_lines(&mut file, writer, (-config.lines) as usize, delim)?
+            }
         } else {
-            let mut line = String::new();
-            let num_lines = if config.lines < 0 {
-                line_count as i128 + config.lines
-            } else { config.lines };
-            for _ in 0..num_lines {
-                let bytes = file.read_line(&mut line)?;
+            let mut buf: Vec<u8> = Vec::new();
+            for _ in 0..config.lines {
+                let bytes = file.read_until(delim, &mut buf)?;
                 if bytes == 0 {
                     break;
                 }
-                print!("{line}");
-                line.clear();
+                writer.write_all(&buf)?;
+                buf.clear();
             }
         }
     }
     Ok(())
 }
 
-fn open(filename: &str) -> RunResult<(Box<dyn BufRead>, usize, usize)> {
+// Streams all but the last `n` lines without knowing the total line count
+// up front, so it works on non-seekable sources like stdin.
+fn print_all_but_last_n_lines(
+    file: &mut Box<dyn BufRead>,
+    writer: &mut impl Write,
+    n: usize,
+    delim: u8
+) -> RunResult<()> {
+    let mut ring: VecDeque<Vec<u8>> = VecDeque::with_capacity(n + 1);
+    let mut buf: Vec<u8> = Vec::new();
+    loop {
+        let bytes = file.read_until(delim, &mut buf)?;
+        if bytes == 0 {
+            break;
+        }
+        ring.push_back(buf.clone());
+        buf.clear();
+        if ring.len() > n {
+            writer.write_all(&ring.pop_front().unwrap())?;
+        }
+    }
+    Ok(())
+}
+
+// Streams all but the last `n` bytes through a fixed-size ring buffer, so it
+// works on non-seekable sources like stdin without buffering the whole input.
+fn print_all_but_last_n_bytes(
+    file: &mut Box<dyn BufRead>,
+    writer: &mut impl Write,
+    n: usize
+) -> RunResult<()> {
+    let mut ring: VecDeque<u8> = VecDeque::with_capacity(n);
+    for byte in file.bytes() {
+        ring.push_back(byte?);
+        if ring.len() > n {
+            writer.write_all(&[ring.pop_front().unwrap()])?;
+        }
+    }
+    Ok(())
+}
+
+fn open(filename: &str, need_line_count: bool, delim: u8) -> RunResult<OpenResult> {
     if let "-" = filename {
         Ok((
         Box::new(BufReader::new(io::stdin())),
-        0,
-        1
+        None,
+        None
     ))
     } else {
-        let line_count = BufReader::new(File::open(filename)?).lines().count();
-        let file = File::open(filename)?;
+        let mut file = File::open(filename)?;
         let size = file.metadata()?.len();
-        
+
+        let line_count = if need_line_count {
+            let mut reader = BufReader::new(&file);
+            let mut buf: Vec<u8> = Vec::new();
+            let mut count = 0;
+            while reader.read_until(delim, &mut buf)? > 0 {
+                count += 1;
+                buf.clear();
+            }
+            file.seek(SeekFrom::Start(0))?;
+            Some(count)
+        } else {
+            None
+        };
+
         Ok((
             Box::new(BufReader::new(file)),
-            size as usize,
+            Some(size as usize),
             line_count
         ))
     }
@@ -150,31 +233,51 @@ fn parse(val: &str) -> RunResult<i128> {
     let scale: i128;
     let mut len = val.len();
 
-    match val.chars().last().unwrap() {
-        'b' => {
-            scale = 512;
-            len -= 1;
-        },
-        'B' => {
-            len -= 2;
-            match val[0..=len].chars().last().unwrap() {
-                'k' => scale = 1000,
-                c => if let Some(n) = MAP.find(c) {
-                    scale = 10_i128.pow(n as u32);
-                } else { return Err(val.into()) }
-            }
+    let Some(last) = val.chars().last() else {
+        return Err(val.into());
+    };
+
+    if let Some(prefix) = val.strip_suffix("iB") {
+        // IEC binary suffixes: KiB, MiB, GiB, ... (same powers of 1024 as the
+        // bare-letter form, just spelled out unambiguously).
+        match prefix.chars().last() {
+            Some(c) => if let Some(n) = MAP.find(c) {
+                scale = 1 << (10 * (n + 1));
+                len -= 3;
+            } else { return Err(val.into()) },
+            None => return Err(val.into())
         }
+    } else {
+        match last {
+            'b' => {
+                scale = 512;
+                len -= 1;
+            },
+            'B' => {
+                if len < 2 {
+                    return Err(val.into());
+                }
+                len -= 2;
+                match val[0..=len].chars().last() {
+                    Some('k') => scale = 1000,
+                    Some(c) => if let Some(n) = MAP.find(c) {
+                        scale = 10_i128.pow(n as u32);
+                    } else { return Err(val.into()) },
+                    None => return Err(val.into())
+                }
+            }
 
-        c => if let Some(n) = MAP.find(c) {
-            scale = 1 << (10 * (n + 1));
-            len -= 1;
-        } else { scale = 1; }
+            c => if let Some(n) = MAP.find(c) {
+                scale = 1 << (10 * (n + 1));
+                len -= 1;
+            } else { scale = 1; }
+        }
     }
 
     match val[0..len].parse::<i128>() {
         Ok(n) => Ok(scale * n),
         _ => Err(val.into())
-    }    
+    }
 }
 
 #[test]
@@ -194,4 +297,141 @@ fn test_parse() {
     let res = parse("foo");
     assert!(res.is_err());
     assert_eq!(res.unwrap_err().to_string(), "foo".to_string());
+
+    let res = parse("2KiB");
+    assert!(res.is_ok());
+    assert_eq!(res.unwrap(), 2 << 10);
+
+    let res = parse("1MiB");
+    assert!(res.is_ok());
+    assert_eq!(res.unwrap(), 1 << 20);
+
+    let res = parse("5b");
+    assert!(res.is_ok());
+    assert_eq!(res.unwrap(), 5 * 512);
+
+    let res = parse("B");
+    assert!(res.is_err());
+
+    let res = parse("");
+    assert!(res.is_err());
+}
+
+#[cfg(test)]
+fn write_temp_file(name: &str, contents: &[u8]) -> String {
+    let path = std::env::temp_dir()
+        .join(format!("headr_test_{name}_{}", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_run_plain_lines() {
+    let path = write_temp_file("plain_lines", b"one\ntwo\nthree\nfour\nfive\n");
+    let config = Config {
+        files: vec![path.clone()],
+        lines: 3,
+        bytes: None,
+        print_header: HeaderChoice::Never,
+        zero_terminated: false
+    };
+    let mut out = Vec::new();
+    run(config, &mut out).unwrap();
+    assert_eq!(out, b"one\ntwo\nthree\n");
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_run_negative_lines() {
+    let path = write_temp_file("negative_lines", b"one\ntwo\nthree\nfour\nfive\n");
+    let config = Config {
+        files: vec![path.clone()],
+        lines: -2,
+        bytes: None,
+        print_header: HeaderChoice::Never,
+        zero_terminated: false
+    };
+    let mut out = Vec::new();
+    run(config, &mut out).unwrap();
+    assert_eq!(out, b"one\ntwo\nthree\n");
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_run_zero_terminated_preserves_bytes() {
+    // Non-UTF-8 bytes must pass through untouched, e.g. arbitrary filenames
+    // from `find -print0`.
+    let path = write_temp_file(
+        "zero_terminated",
+        b"\xff\xfe\x00hello\x00world\x00"
+    );
+    let config = Config {
+        files: vec![path.clone()],
+        lines: 2,
+        bytes: None,
+        print_header: HeaderChoice::Never,
+        zero_terminated: true
+    };
+    let mut out = Vec::new();
+    run(config, &mut out).unwrap();
+    assert_eq!(out, b"\xff\xfe\x00hello\x00");
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_run_negative_bytes() {
+    let path = write_temp_file("negative_bytes", b"0123456789");
+    let config = Config {
+        files: vec![path.clone()],
+        lines: 10,
+        bytes: Some(-4),
+        print_header: HeaderChoice::Never,
+        zero_terminated: false
+    };
+    let mut out = Vec::new();
+    run(config, &mut out).unwrap();
+    assert_eq!(out, b"012345");
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_run_negative_bytes_exceeds_file_size() {
+    let path = write_temp_file("negative_bytes_exceeds", b"abc");
+    let config = Config {
+        files: vec![path.clone()],
+        lines: 10,
+        bytes: Some(-10),
+        print_header: HeaderChoice::Never,
+        zero_terminated: false
+    };
+    let mut out = Vec::new();
+    run(config, &mut out).unwrap();
+    assert_eq!(out, b"");
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_print_all_but_last_n_lines_ring_buffer() {
+    let mut reader: Box<dyn BufRead> =
+        Box::new(io::Cursor::new(b"one\ntwo\nthree\nfour\nfive\n".to_vec()));
+    let mut out = Vec::new();
+    print_all_but_last_n_lines(&mut reader, &mut out, 2, b'\n').unwrap();
+    assert_eq!(out, b"one\ntwo\nthree\n");
+}
+
+#[test]
+fn test_print_all_but_last_n_bytes_ring_buffer() {
+    let mut reader: Box<dyn BufRead> =
+        Box::new(io::Cursor::new(b"0123456789".to_vec()));
+    let mut out = Vec::new();
+    print_all_but_last_n_bytes(&mut reader, &mut out, 4).unwrap();
+    assert_eq!(out, b"012345");
+}
+
+#[test]
+fn test_print_all_but_last_n_bytes_exceeds_input() {
+    let mut reader: Box<dyn BufRead> = Box::new(io::Cursor::new(b"abc".to_vec()));
+    let mut out = Vec::new();
+    print_all_but_last_n_bytes(&mut reader, &mut out, 10).unwrap();
+    assert_eq!(out, b"");
 }
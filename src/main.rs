@@ -0,0 +1,13 @@
+use std::io::{self, BufWriter, Write};
+
+fn main() {
+    let mut writer: BufWriter<io::StdoutLock> = BufWriter::new(io::stdout().lock());
+    let result = headr::get_args()
+        .and_then(|config| headr::run(config, &mut writer))
+        .and_then(|()| writer.flush().map_err(Into::into));
+
+    if let Err(err) = result {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}